@@ -0,0 +1,68 @@
+//! Application configuration, loaded from a TOML file (`config.toml` by
+//! default).
+
+use std::path::{Path, PathBuf};
+
+use anyhow::Context;
+use libgitcash::Account;
+use serde::Deserialize;
+
+/// Currency formatting/parsing rules: a code, a display symbol, and how
+/// many minor-unit digits amounts are stored with (e.g. 2 for cents).
+#[derive(Debug, Clone, Deserialize)]
+pub struct Currency {
+    pub code: String,
+    pub symbol: String,
+    pub exponent: u32,
+}
+
+impl Default for Currency {
+    /// Swiss francs, the original hardcoded default.
+    fn default() -> Self {
+        Self {
+            code: "CHF".to_string(),
+            symbol: "CHF".to_string(),
+            exponent: 2,
+        }
+    }
+}
+
+/// The on-disk shape of `config.toml`.
+#[derive(Debug, Deserialize)]
+struct RawConfig {
+    repo_path: PathBuf,
+    account: String,
+    git_name: String,
+    #[serde(default)]
+    currency: Currency,
+}
+
+/// Top-level GitCash configuration.
+#[derive(Debug, Clone)]
+pub struct Config {
+    /// Where the ledger's git repository lives.
+    pub repo_path: PathBuf,
+    /// The shop's own account, credited by `pay` and EAN purchases.
+    pub account: Account,
+    /// Display name used to greet the operator in the interactive CLI.
+    pub git_name: String,
+    /// Currency formatting/parsing rules.
+    pub currency: Currency,
+}
+
+impl Config {
+    /// Load configuration from the TOML file at `path`.
+    pub fn load(path: impl AsRef<Path>) -> anyhow::Result<Self> {
+        let path = path.as_ref();
+        let contents = std::fs::read_to_string(path)
+            .with_context(|| format!("reading config at {}", path.display()))?;
+        let raw: RawConfig = toml::from_str(&contents)
+            .with_context(|| format!("parsing config at {}", path.display()))?;
+        Ok(Self {
+            repo_path: raw.repo_path,
+            account: Account::shop(raw.account)?,
+            git_name: raw.git_name,
+            currency: raw.currency,
+        })
+    }
+}
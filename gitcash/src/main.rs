@@ -1,13 +1,14 @@
 use std::{path::PathBuf, sync::Arc};
 
 use anyhow::{anyhow, Context};
-use clap::{Parser, Subcommand};
+use clap::{Parser, Subcommand, ValueEnum};
 use config::Config;
 use inquire::{
     validator::{ErrorMessage, Validation},
     Autocomplete,
 };
-use libgitcash::{Account, AccountType, Repo, Transaction};
+use libgitcash::{Account, AccountType, Repo, Transaction, TransactionRecord};
+use serde::Serialize;
 use tracing::metadata::LevelFilter;
 
 mod config;
@@ -18,10 +19,73 @@ struct Args {
     #[arg(short, long, default_value = "config.toml")]
     config: PathBuf,
 
+    /// Output format for reporting commands
+    #[arg(long, value_enum, default_value_t = OutputFormat::Display)]
+    output: OutputFormat,
+
     #[command(subcommand)]
     command: Command,
 }
 
+/// How reporting commands render their results.
+#[derive(Clone, Copy, Debug, Default, ValueEnum)]
+enum OutputFormat {
+    /// Human-readable table
+    #[default]
+    Display,
+    /// Pretty-printed JSON
+    Json,
+    /// YAML
+    Yaml,
+}
+
+impl OutputFormat {
+    /// Render `value` either with the human-readable `display` callback or,
+    /// when a machine format is selected, by serializing `value` via serde.
+    fn render<T: Serialize>(self, value: &T, display: impl FnOnce()) -> anyhow::Result<()> {
+        match self {
+            OutputFormat::Display => display(),
+            OutputFormat::Json => println!("{}", serde_json::to_string_pretty(value)?),
+            OutputFormat::Yaml => print!("{}", serde_yaml::to_string(value)?),
+        }
+        Ok(())
+    }
+}
+
+/// Serializable view of an [`Account`] for machine-readable output.
+#[derive(Serialize)]
+struct AccountView {
+    name: String,
+    account_type: String,
+}
+
+impl From<&Account> for AccountView {
+    fn from(account: &Account) -> Self {
+        Self {
+            name: account.name.clone(),
+            account_type: format!("{:?}", account.account_type),
+        }
+    }
+}
+
+/// Serializable view of an account balance for machine-readable output.
+#[derive(Serialize)]
+struct BalanceView {
+    name: String,
+    account_type: String,
+    balance: i64,
+}
+
+impl From<&(Account, i64)> for BalanceView {
+    fn from((account, balance): &(Account, i64)) -> Self {
+        Self {
+            name: account.name.clone(),
+            account_type: format!("{:?}", account.account_type),
+            balance: *balance,
+        }
+    }
+}
+
 #[derive(Subcommand)]
 enum Command {
     /// List all accounts
@@ -31,10 +95,123 @@ enum Command {
     /// List all user accounts with negative balances
     Shame,
 
+    /// Show the transaction history in chronological order
+    Log {
+        /// Only show transactions touching this account
+        #[arg(long)]
+        account: Option<String>,
+        /// Keep running and stream new transactions as they are committed
+        #[arg(long)]
+        follow: bool,
+    },
+
+    /// Replay the transaction history and check it for corruption
+    Verify {
+        /// Flag user accounts whose balance drops below this limit (in the configured currency)
+        #[arg(long)]
+        credit_limit: Option<f32>,
+    },
+
+    /// Charge a user: they pay the configured shop account
+    Pay {
+        #[arg(long)]
+        user: String,
+        #[arg(long)]
+        amount: f32,
+    },
+    /// Top up a user's balance from the cash account
+    Deposit {
+        #[arg(long)]
+        user: String,
+        #[arg(long)]
+        amount: f32,
+    },
+    /// Move money from one existing user account to another
+    Transfer {
+        #[arg(long)]
+        from: String,
+        #[arg(long)]
+        to: String,
+        #[arg(long)]
+        amount: f32,
+    },
+
     /// Interactive CLI
     Cli,
 }
 
+/// Format an amount of minor units using the configured currency, e.g.
+/// `250` with exponent 2 and symbol `CHF` becomes `2.50 CHF`.
+fn format_amount(currency: &config::Currency, minor_units: i64) -> String {
+    let scale = 10_f64.powi(currency.exponent as i32);
+    format!(
+        "{:.*} {}",
+        currency.exponent as usize,
+        minor_units as f64 / scale,
+        currency.symbol
+    )
+}
+
+/// Parse a human-entered amount (e.g. `2.50`) into integer minor units.
+fn parse_amount(repo: &Repo, currency: &config::Currency, input: &str) -> anyhow::Result<i64> {
+    let value: f64 = input
+        .trim()
+        .parse()
+        .context(format!("Invalid amount: {}", input))?;
+    Ok(repo.convert_amount(currency.exponent, value))
+}
+
+/// Names of all user accounts in the repo.
+fn user_account_names(repo: &Repo) -> Vec<String> {
+    repo.accounts()
+        .into_iter()
+        .filter(|account| account.account_type == AccountType::User)
+        .map(|account| account.name)
+        .collect()
+}
+
+/// Ensure `name` refers to a user account that already exists.
+fn existing_username(usernames: &[String], name: &str) -> anyhow::Result<()> {
+    if usernames.iter().any(|known| known == name) {
+        Ok(())
+    } else {
+        Err(anyhow!("Not a known username: {}", name))
+    }
+}
+
+/// Validate a prospective new username, returning the trimmed name.
+fn new_username(usernames: &[String], name: &str) -> anyhow::Result<String> {
+    let name = name.trim();
+    if name.is_empty() {
+        Err(anyhow!("Username may not be empty"))
+    } else if name.contains(' ') {
+        Err(anyhow!("Username may not contain a space"))
+    } else if name.contains(':') {
+        Err(anyhow!("Username may not contain a colon"))
+    } else if usernames.iter().any(|known| known == name) {
+        Err(anyhow!("Username already exists: {}", name))
+    } else {
+        Ok(name.to_string())
+    }
+}
+
+/// Print a single transaction record as one history line.
+fn print_transaction(currency: &config::Currency, record: &TransactionRecord) {
+    let tx = &record.transaction;
+    println!(
+        "{} {:>10} {} -> {} {}{}",
+        &record.hash[..record.hash.len().min(8)],
+        record.timestamp,
+        tx.from.name,
+        tx.to.name,
+        format_amount(currency, tx.amount),
+        tx.description
+            .as_ref()
+            .map(|description| format!("  {}", description))
+            .unwrap_or_default(),
+    );
+}
+
 #[derive(Clone)]
 struct CommandSuggester {
     commands: Vec<&'static str>,
@@ -96,6 +273,44 @@ impl TryFrom<&str> for CliCommand {
     }
 }
 
+/// Check whether the given input is a valid EAN-8 or EAN-13 barcode.
+///
+/// Returns the barcode if every character is a digit, the length is 8 or 13
+/// and the trailing check digit matches the GS1 checksum; otherwise `None`.
+/// EAN-13 weights the 12 body digits in even positions (counting from 1) by
+/// 3 and the rest by 1. EAN-8 has one fewer data digit, so GS1 flips that
+/// parity: its 7 body digits are weighted 3 in *odd* positions and 1 in
+/// even ones. Either way the check digit must equal
+/// `(10 - (sum mod 10)) mod 10`.
+fn valid_ean(input: &str) -> Option<&str> {
+    if !matches!(input.len(), 8 | 13) || !input.bytes().all(|b| b.is_ascii_digit()) {
+        return None;
+    }
+    let digits: Vec<u32> = input
+        .chars()
+        .map(|c| c.to_digit(10).expect("already checked to be a digit"))
+        .collect();
+    let (body, check) = digits.split_at(digits.len() - 1);
+    let even_position_weight = if body.len() == 12 { 3 } else { 1 };
+    let odd_position_weight = if body.len() == 12 { 1 } else { 3 };
+    let sum: u32 = body
+        .iter()
+        .enumerate()
+        .map(|(i, d)| {
+            if (i + 1) % 2 == 0 {
+                d * even_position_weight
+            } else {
+                d * odd_position_weight
+            }
+        })
+        .sum();
+    if (10 - (sum % 10)) % 10 == check[0] {
+        Some(input)
+    } else {
+        None
+    }
+}
+
 pub fn main() -> anyhow::Result<()> {
     // Initialize logging subscriber
     let subscriber = tracing_subscriber::fmt()
@@ -115,24 +330,31 @@ pub fn main() -> anyhow::Result<()> {
     // Run command
     match args.command {
         Command::Accounts => {
-            println!("Accounts:");
-            for account in repo.accounts() {
-                println!("- Account: {} ({:?})", account.name, account.account_type);
-            }
+            let accounts = repo.accounts();
+            let view = accounts.iter().map(AccountView::from).collect::<Vec<_>>();
+            args.output.render(&view, || {
+                println!("Accounts:");
+                for account in &accounts {
+                    println!("- Account: {} ({:?})", account.name, account.account_type);
+                }
+            })?;
         }
         Command::Balances => {
-            println!("Balances:");
-            for (account, balance) in repo.balances() {
-                println!(
-                    "- {}: {:.2} CHF [{:?}]",
-                    account.name,
-                    balance as f32 / 100.0,
-                    account.account_type
-                );
-            }
+            let balances = repo.balances();
+            let view = balances.iter().map(BalanceView::from).collect::<Vec<_>>();
+            args.output.render(&view, || {
+                println!("Balances:");
+                for (account, balance) in &balances {
+                    println!(
+                        "- {}: {} [{:?}]",
+                        account.name,
+                        format_amount(&config.currency, *balance),
+                        account.account_type
+                    );
+                }
+            })?;
         }
         Command::Shame => {
-            println!("Wall of shame (negative user balances):");
             let negative_balance_accounts = repo
                 .balances()
                 .into_iter()
@@ -140,67 +362,179 @@ pub fn main() -> anyhow::Result<()> {
                     account.account_type == AccountType::User && *balance < 0
                 })
                 .collect::<Vec<_>>();
-            for (account, balance) in &negative_balance_accounts {
-                println!(
-                    "- {}: {:.2} CHF [{:?}]",
-                    account.name,
-                    *balance as f32 / 100.0,
-                    account.account_type
-                );
+            let view = negative_balance_accounts
+                .iter()
+                .map(BalanceView::from)
+                .collect::<Vec<_>>();
+            args.output.render(&view, || {
+                println!("Wall of shame (negative user balances):");
+                for (account, balance) in &negative_balance_accounts {
+                    println!(
+                        "- {}: {} [{:?}]",
+                        account.name,
+                        format_amount(&config.currency, *balance),
+                        account.account_type
+                    );
+                }
+                if negative_balance_accounts.is_empty() {
+                    println!("None at all! 🎉");
+                }
+            })?;
+        }
+        Command::Log { account, follow } => {
+            let touches = |record: &TransactionRecord| match &account {
+                Some(name) => {
+                    record.transaction.from.name == *name || record.transaction.to.name == *name
+                }
+                None => true,
+            };
+
+            // Print the existing history, remembering which commits we have
+            // already shown so follow mode only streams new ones.
+            let mut seen = std::collections::HashSet::new();
+            for record in repo.transactions()? {
+                if touches(&record) {
+                    print_transaction(&config.currency, &record);
+                }
+                seen.insert(record.hash);
+            }
+
+            while follow {
+                std::thread::sleep(std::time::Duration::from_secs(1));
+                for record in repo.transactions()? {
+                    if seen.insert(record.hash.clone()) && touches(&record) {
+                        print_transaction(&config.currency, &record);
+                    }
+                }
+            }
+        }
+        Command::Verify { credit_limit } => {
+            let credit_limit =
+                credit_limit.map(|limit| repo.convert_amount(config.currency.exponent, limit as f64));
+
+            // Re-derive every balance by replaying the commit chain, checking
+            // that a user account only pays after it has appeared in an
+            // earlier transaction. Only the paying side of user transactions
+            // is checked: an account's first appearance as recipient counts
+            // as its creation, and shop and source payers are not checked.
+            let mut balances: std::collections::HashMap<String, i64> =
+                std::collections::HashMap::new();
+            let mut known: std::collections::HashSet<String> = std::collections::HashSet::new();
+            let mut errors: Vec<String> = Vec::new();
+
+            for entry in repo.transaction_log()? {
+                let short = &entry.hash[..entry.hash.len().min(8)];
+                let tx = match entry.transaction {
+                    Ok(tx) => tx,
+                    Err(err) => {
+                        errors.push(format!("{}: malformed transaction: {}", short, err));
+                        continue;
+                    }
+                };
+                if tx.from.account_type == AccountType::User && !known.contains(&tx.from.name) {
+                    errors.push(format!(
+                        "{}: references user account {} before it was created",
+                        short, tx.from.name
+                    ));
+                }
+                known.insert(tx.from.name.clone());
+                known.insert(tx.to.name.clone());
+                *balances.entry(tx.from.name.clone()).or_default() -= tx.amount;
+                *balances.entry(tx.to.name.clone()).or_default() += tx.amount;
+            }
+
+            // Confirm the replayed balances match the ones the repo reports and
+            // that no user dropped below the configured credit limit.
+            for (account, balance) in repo.balances() {
+                let replayed = balances.get(&account.name).copied().unwrap_or(0);
+                if replayed != balance {
+                    errors.push(format!(
+                        "balance mismatch for {}: replayed {} but repo reports {}",
+                        account.name, replayed, balance
+                    ));
+                }
+                if account.account_type == AccountType::User {
+                    if let Some(limit) = credit_limit {
+                        if balance < limit {
+                            errors.push(format!(
+                                "{} is below the credit limit: {}",
+                                account.name,
+                                format_amount(&config.currency, balance)
+                            ));
+                        }
+                    }
+                }
             }
-            if negative_balance_accounts.is_empty() {
-                println!("None at all! 🎉");
+
+            if errors.is_empty() {
+                println!("Ledger OK");
+            } else {
+                for error in &errors {
+                    eprintln!("error: {}", error);
+                }
+                std::process::exit(1);
             }
         }
+        Command::Pay { user, amount } => {
+            let usernames = user_account_names(&repo);
+            existing_username(&usernames, &user)?;
+            let id = repo.create_transaction(&Transaction {
+                from: Account::user(user)?,
+                to: config.account.clone(),
+                amount: repo.convert_amount(config.currency.exponent, amount as f64),
+                description: None,
+                meta: None,
+            })?;
+            println!("{}", id);
+        }
+        Command::Deposit { user, amount } => {
+            let usernames = user_account_names(&repo);
+            existing_username(&usernames, &user)?;
+            let id = repo.create_transaction(&Transaction {
+                from: Account::source("cash")?,
+                to: Account::user(user)?,
+                amount: repo.convert_amount(config.currency.exponent, amount as f64),
+                description: None,
+                meta: None,
+            })?;
+            println!("{}", id);
+        }
+        Command::Transfer { from, to, amount } => {
+            let usernames = user_account_names(&repo);
+            existing_username(&usernames, &from)?;
+            existing_username(&usernames, &to)?;
+            let id = repo.create_transaction(&Transaction {
+                from: Account::user(from)?,
+                to: Account::user(to)?,
+                amount: repo.convert_amount(config.currency.exponent, amount as f64),
+                description: None,
+                meta: None,
+            })?;
+            println!("{}", id);
+        }
         Command::Cli => {
             println!("Welcome to the GitCash CLI for {}!", config.git_name);
 
             // Get list of valid user account names
-            let usernames = Arc::new(
-                repo.accounts()
-                    .into_iter()
-                    .filter(|acc| acc.account_type == AccountType::User)
-                    .map(|acc| acc.name)
-                    .collect::<Vec<_>>(),
-            );
-
-            // Validators
+            let usernames = Arc::new(user_account_names(&repo));
+
+            // Validators, sharing the same logic as the non-interactive
+            // subcommands but surfacing errors as inquire validations.
             let existing_username_validator = {
                 let usernames = usernames.clone();
                 move |value: &str| {
-                    Ok(if usernames.iter().any(|name| name == value) {
-                        Validation::Valid
-                    } else {
-                        Validation::Invalid(ErrorMessage::Custom(format!(
-                            "Not a known username: {}",
-                            value
-                        )))
+                    Ok(match existing_username(&usernames, value) {
+                        Ok(()) => Validation::Valid,
+                        Err(err) => Validation::Invalid(ErrorMessage::Custom(err.to_string())),
                     })
                 }
             };
             let new_username_validator = {
                 let usernames = usernames.clone();
                 move |value: &str| {
-                    let value = value.trim();
-                    Ok(if value.is_empty() {
-                        Validation::Invalid(ErrorMessage::Custom(
-                            "Username may not be empty".into(),
-                        ))
-                    } else if value.contains(' ') {
-                        Validation::Invalid(ErrorMessage::Custom(
-                            "Username may not contain a space".into(),
-                        ))
-                    } else if value.contains(':') {
-                        Validation::Invalid(ErrorMessage::Custom(
-                            "Username may not contain a colon".into(),
-                        ))
-                    } else if usernames.iter().any(|name| name == value) {
-                        Validation::Invalid(ErrorMessage::Custom(format!(
-                            "Username already exists: {}",
-                            value
-                        )))
-                    } else {
-                        Validation::Valid
+                    Ok(match new_username(&usernames, value) {
+                        Ok(_) => Validation::Valid,
+                        Err(err) => Validation::Invalid(ErrorMessage::Custom(err.to_string())),
                     })
                 }
             };
@@ -222,8 +556,9 @@ pub fn main() -> anyhow::Result<()> {
 
             loop {
                 // First, ask for command, product or amount
+                let placeholder = format!("e.g. 2.50 {}", config.currency.symbol);
                 let target = inquire::Text::new("Amount, EAN or command:")
-                    .with_placeholder("e.g. 2.50 CHF")
+                    .with_placeholder(&placeholder)
                     .with_autocomplete(CommandSuggester::new(&commands))
                     .prompt()?;
 
@@ -252,19 +587,48 @@ pub fn main() -> anyhow::Result<()> {
                     Err(_) => {}
                 };
 
+                // Not a command. If it looks like an EAN barcode, resolve it
+                // to a product from the catalog and charge its price.
+                if let Some(ean) = valid_ean(&target) {
+                    let Some(product) = repo.product_by_ean(ean) else {
+                        println!("No product found for EAN {}", ean);
+                        continue;
+                    };
+                    let name = inquire::Text::new("Name:")
+                        .with_autocomplete(name_suggester.clone())
+                        .with_validator(existing_username_validator.clone())
+                        .prompt()?;
+                    println!(
+                        "Creating transaction: {} buys {} for {}",
+                        name,
+                        product.name,
+                        format_amount(&config.currency, product.price)
+                    );
+                    repo.create_transaction(&Transaction {
+                        from: Account::user(name)?,
+                        to: config.account.clone(),
+                        amount: product.price,
+                        description: Some(product.name.clone()),
+                        meta: Some(format!("ean={}", ean)),
+                    })?;
+                    continue;
+                }
+
                 // Not a command, treat it as amount
-                let amount: f32 = target
-                    .parse()
-                    .context(format!("Invalid amount: {}", target))?;
+                let amount = parse_amount(&repo, &config.currency, &target)?;
                 let name = inquire::Text::new("Name:")
                     .with_autocomplete(name_suggester.clone())
                     .with_validator(existing_username_validator.clone())
                     .prompt()?;
-                println!("Creating transaction: {} pays {:.2} CHF", name, amount);
+                println!(
+                    "Creating transaction: {} pays {}",
+                    name,
+                    format_amount(&config.currency, amount)
+                );
                 repo.create_transaction(&Transaction {
                     from: Account::user(name)?,
                     to: config.account.clone(),
-                    amount: repo.convert_amount(amount),
+                    amount,
                     description: None,
                     meta: None,
                 })?;
@@ -274,3 +638,34 @@ pub fn main() -> anyhow::Result<()> {
 
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::valid_ean;
+
+    #[test]
+    fn accepts_valid_ean_13() {
+        // https://en.wikipedia.org/wiki/International_Article_Number, a
+        // commonly cited GS1 test vector.
+        assert_eq!(valid_ean("4006381333931"), Some("4006381333931"));
+    }
+
+    #[test]
+    fn accepts_valid_ean_8() {
+        // Body 9638507 checksums to 4, the opposite odd/even weighting from
+        // EAN-13 since EAN-8 has one fewer data digit.
+        assert_eq!(valid_ean("96385074"), Some("96385074"));
+    }
+
+    #[test]
+    fn rejects_wrong_check_digit() {
+        assert_eq!(valid_ean("4006381333930"), None);
+        assert_eq!(valid_ean("96385070"), None);
+    }
+
+    #[test]
+    fn rejects_non_digit_or_wrong_length() {
+        assert_eq!(valid_ean("400638133393a"), None);
+        assert_eq!(valid_ean("12345"), None);
+    }
+}
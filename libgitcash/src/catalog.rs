@@ -0,0 +1,50 @@
+//! The shop's product catalog: items sellable by scanning a barcode.
+
+use std::fs;
+
+use anyhow::Context;
+use serde::Deserialize;
+
+use crate::Repo;
+
+/// An item the shop sells, keyed by its barcode.
+#[derive(Debug, Clone, Deserialize)]
+pub struct Product {
+    pub ean: String,
+    pub name: String,
+    /// Price in the ledger's minor currency units (e.g. cents).
+    pub price: i64,
+}
+
+/// The on-disk shape of `products.toml`: a flat list of products under a
+/// `[[product]]` table array.
+#[derive(Debug, Default, Deserialize)]
+struct CatalogFile {
+    #[serde(default)]
+    product: Vec<Product>,
+}
+
+impl Repo {
+    /// All products in the catalog, as stored in `products.toml` at the
+    /// repo root. An absent file means an empty catalog rather than an
+    /// error, since not every shop sells scannable products.
+    pub fn products(&self) -> anyhow::Result<Vec<Product>> {
+        let path = self.path().join("products.toml");
+        if !path.exists() {
+            return Ok(Vec::new());
+        }
+        let contents = fs::read_to_string(&path)
+            .with_context(|| format!("reading catalog at {}", path.display()))?;
+        let catalog: CatalogFile = toml::from_str(&contents)
+            .with_context(|| format!("parsing catalog at {}", path.display()))?;
+        Ok(catalog.product)
+    }
+
+    /// Look up a single product by its EAN-8/EAN-13 barcode.
+    pub fn product_by_ean(&self, ean: &str) -> Option<Product> {
+        self.products()
+            .ok()?
+            .into_iter()
+            .find(|product| product.ean == ean)
+    }
+}
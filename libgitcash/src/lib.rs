@@ -0,0 +1,199 @@
+//! Core ledger types: a GitCash repo is a plain git repository where every
+//! transaction is recorded as its own commit on `main`.
+
+use std::path::{Path, PathBuf};
+
+use anyhow::Context;
+use serde::{Deserialize, Serialize};
+
+mod catalog;
+mod log;
+mod payload;
+
+pub use catalog::Product;
+pub use log::LogEntry;
+
+pub(crate) const BRANCH: &str = "main";
+
+/// The kind of account a ledger entry can reference.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum AccountType {
+    /// A person with a running balance.
+    User,
+    /// The shop's own till, credited by purchases.
+    Shop,
+    /// An external source of funds, e.g. cash handed to the operator.
+    Source,
+}
+
+/// An account referenced by a [`Transaction`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Account {
+    pub name: String,
+    pub account_type: AccountType,
+}
+
+impl Account {
+    /// A user account, e.g. the payer in a `pay` transaction.
+    pub fn user(name: impl Into<String>) -> anyhow::Result<Self> {
+        Ok(Self {
+            name: name.into(),
+            account_type: AccountType::User,
+        })
+    }
+
+    /// An external source account, e.g. `cash`.
+    pub fn source(name: impl Into<String>) -> anyhow::Result<Self> {
+        Ok(Self {
+            name: name.into(),
+            account_type: AccountType::Source,
+        })
+    }
+
+    /// The shop's own account, credited by `pay` and EAN purchases.
+    pub fn shop(name: impl Into<String>) -> anyhow::Result<Self> {
+        Ok(Self {
+            name: name.into(),
+            account_type: AccountType::Shop,
+        })
+    }
+}
+
+/// A single movement of funds from one account to another, expressed in the
+/// ledger's minor currency units (e.g. cents).
+#[derive(Debug, Clone)]
+pub struct Transaction {
+    pub from: Account,
+    pub to: Account,
+    pub amount: i64,
+    pub description: Option<String>,
+    pub meta: Option<String>,
+}
+
+/// A [`Transaction`] as recorded in the git history, together with the
+/// commit it lives in.
+#[derive(Debug, Clone)]
+pub struct TransactionRecord {
+    pub hash: String,
+    pub timestamp: i64,
+    pub transaction: Transaction,
+}
+
+/// A git-backed ledger: every transaction is its own commit on `main`.
+pub struct Repo {
+    path: PathBuf,
+    git: git2::Repository,
+}
+
+impl Repo {
+    /// Open the ledger at `path`, initializing an empty one if it doesn't
+    /// exist yet.
+    pub fn open(path: impl AsRef<Path>) -> anyhow::Result<Self> {
+        let path = path.as_ref().to_path_buf();
+        let git = match git2::Repository::open(&path) {
+            Ok(git) => git,
+            Err(_) => git2::Repository::init(&path)
+                .with_context(|| format!("initializing ledger at {}", path.display()))?,
+        };
+        Ok(Self { path, git })
+    }
+
+    /// The directory the ledger is checked out in.
+    pub(crate) fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// The underlying git repository backing this ledger.
+    pub(crate) fn git(&self) -> &git2::Repository {
+        &self.git
+    }
+
+    /// The transaction of every well-formed commit in the history, in
+    /// chronological order. Unlike [`Repo::transactions`], a malformed
+    /// commit is skipped rather than failing the whole result — used
+    /// by [`Repo::accounts`]/[`Repo::balances`], which have no way to
+    /// surface an error through their return type.
+    fn well_formed_transactions(&self) -> Vec<Transaction> {
+        self.transaction_log()
+            .unwrap_or_default()
+            .into_iter()
+            .filter_map(|entry| entry.transaction.ok())
+            .collect()
+    }
+
+    /// Every account that has appeared on either side of a transaction.
+    pub fn accounts(&self) -> Vec<Account> {
+        let mut seen = std::collections::HashSet::new();
+        let mut accounts = Vec::new();
+        for transaction in self.well_formed_transactions() {
+            for account in [transaction.from, transaction.to] {
+                if seen.insert(account.name.clone()) {
+                    accounts.push(account);
+                }
+            }
+        }
+        accounts
+    }
+
+    /// Every account's current balance, derived by replaying the full
+    /// transaction history.
+    pub fn balances(&self) -> Vec<(Account, i64)> {
+        let mut balances: Vec<(Account, i64)> = self
+            .accounts()
+            .into_iter()
+            .map(|account| (account, 0))
+            .collect();
+        let index = |balances: &[(Account, i64)], name: &str| {
+            balances.iter().position(|(account, _)| account.name == name)
+        };
+        for tx in self.well_formed_transactions() {
+            if let Some(i) = index(&balances, &tx.from.name) {
+                balances[i].1 -= tx.amount;
+            }
+            if let Some(i) = index(&balances, &tx.to.name) {
+                balances[i].1 += tx.amount;
+            }
+        }
+        balances
+    }
+
+    /// Append a transaction to the ledger as a new commit, returning its
+    /// hash.
+    pub fn create_transaction(&self, transaction: &Transaction) -> anyhow::Result<String> {
+        let signature = self.git.signature().context("building commit signature")?;
+        let tree = match self.git.head().and_then(|head| head.peel_to_tree()) {
+            Ok(tree) => tree,
+            Err(_) => {
+                let oid = self.git.treebuilder(None)?.write()?;
+                self.git.find_tree(oid)?
+            }
+        };
+        let parent = self.git.head().and_then(|head| head.peel_to_commit()).ok();
+        let parents: Vec<&git2::Commit> = parent.iter().collect();
+        let message = format!(
+            "{}\n\n{}",
+            transaction
+                .description
+                .clone()
+                .unwrap_or_else(|| "transaction".to_string()),
+            payload::encode(transaction)?
+        );
+        let oid = self.git.commit(
+            Some(&format!("refs/heads/{}", BRANCH)),
+            &signature,
+            &signature,
+            &message,
+            &tree,
+            &parents,
+        )?;
+        Ok(oid.to_string())
+    }
+
+    /// Convert a human-entered decimal `value` into integer minor units at
+    /// `exponent` digits of precision (e.g. 2 for cents), rounding
+    /// half-to-even to avoid floating-point drift.
+    pub fn convert_amount(&self, exponent: u32, value: f64) -> i64 {
+        let scale = 10_f64.powi(exponent as i32);
+        (value * scale).round_ties_even() as i64
+    }
+}
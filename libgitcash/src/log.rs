@@ -0,0 +1,60 @@
+//! Walking the commit history to recover transactions, tolerant of
+//! malformed commits.
+
+use anyhow::Context;
+
+use crate::{payload, Repo, Transaction, TransactionRecord, BRANCH};
+
+/// One entry of the raw commit history. Unlike [`Repo::transactions`], a
+/// malformed commit does not abort the walk — its parse error is reported
+/// here so callers such as `verify` can flag it and keep replaying the rest
+/// of the history.
+pub struct LogEntry {
+    pub hash: String,
+    pub timestamp: i64,
+    pub transaction: anyhow::Result<Transaction>,
+}
+
+impl Repo {
+    /// The full transaction history in chronological order. Fails on the
+    /// first malformed commit; use [`Repo::transaction_log`] to tolerate and
+    /// report them individually instead.
+    pub fn transactions(&self) -> anyhow::Result<Vec<TransactionRecord>> {
+        self.transaction_log()?
+            .into_iter()
+            .map(|entry| {
+                let transaction = entry
+                    .transaction
+                    .with_context(|| format!("commit {}", entry.hash))?;
+                Ok(TransactionRecord {
+                    hash: entry.hash,
+                    timestamp: entry.timestamp,
+                    transaction,
+                })
+            })
+            .collect()
+    }
+
+    /// The full commit history in chronological order, with each commit's
+    /// transaction payload parsed independently so one malformed commit
+    /// doesn't prevent replaying the rest.
+    pub fn transaction_log(&self) -> anyhow::Result<Vec<LogEntry>> {
+        let Ok(head) = self.git().find_reference(&format!("refs/heads/{}", BRANCH)) else {
+            return Ok(Vec::new());
+        };
+
+        let mut walk = self.git().revwalk()?;
+        walk.push(head.peel_to_commit()?.id())?;
+        walk.set_sorting(git2::Sort::REVERSE | git2::Sort::TIME)?;
+
+        walk.map(|oid| {
+            let commit = self.git().find_commit(oid?)?;
+            Ok(LogEntry {
+                hash: commit.id().to_string(),
+                timestamp: commit.time().seconds(),
+                transaction: payload::decode(commit.message().unwrap_or_default()),
+            })
+        })
+        .collect()
+    }
+}
@@ -0,0 +1,61 @@
+//! On-disk encoding of a [`Transaction`] inside its commit message.
+
+use anyhow::Context;
+use serde::{Deserialize, Serialize};
+
+use crate::{Account, AccountType, Transaction};
+
+#[derive(Serialize, Deserialize)]
+struct AccountPayload {
+    name: String,
+    account_type: AccountType,
+}
+
+#[derive(Serialize, Deserialize)]
+struct TransactionPayload {
+    from: AccountPayload,
+    to: AccountPayload,
+    amount: i64,
+    description: Option<String>,
+    meta: Option<String>,
+}
+
+/// Render `transaction` as the TOML payload stored after the summary line
+/// of its commit message.
+pub(crate) fn encode(transaction: &Transaction) -> anyhow::Result<String> {
+    let payload = TransactionPayload {
+        from: AccountPayload {
+            name: transaction.from.name.clone(),
+            account_type: transaction.from.account_type,
+        },
+        to: AccountPayload {
+            name: transaction.to.name.clone(),
+            account_type: transaction.to.account_type,
+        },
+        amount: transaction.amount,
+        description: transaction.description.clone(),
+        meta: transaction.meta.clone(),
+    };
+    toml::to_string(&payload).context("encoding transaction payload")
+}
+
+/// Parse a transaction back out of a commit message, failing if the part
+/// after the first blank line isn't a well-formed payload.
+pub(crate) fn decode(message: &str) -> anyhow::Result<Transaction> {
+    let body = message.split_once("\n\n").map_or(message, |(_, body)| body);
+    let payload: TransactionPayload =
+        toml::from_str(body).context("parsing transaction payload")?;
+    Ok(Transaction {
+        from: Account {
+            name: payload.from.name,
+            account_type: payload.from.account_type,
+        },
+        to: Account {
+            name: payload.to.name,
+            account_type: payload.to.account_type,
+        },
+        amount: payload.amount,
+        description: payload.description,
+        meta: payload.meta,
+    })
+}